@@ -1,27 +1,50 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use ctrlc;
 use gif::{Encoder as GifEncoder, Frame as GifFrame, Repeat};
 use image::{codecs::png::PngEncoder, Rgb, RgbImage};
-use nix::sys::signal::{kill, Signal};
+use nix::sys::signal::{kill, SigHandler, Signal};
 use nix::unistd::Pid;
 use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
 use rusttype::{Font, Scale};
 use serde::{Deserialize, Serialize};
 use std::mem::MaybeUninit;
 use std::{
+    collections::HashMap,
     fs::{self, File, OpenOptions},
     io::{self, Read, Write},
     path::Path,
     process::exit,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
     thread,
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use terminal_size::{terminal_size, Height, Width};
 
 // Constants for file paths
 const PID_FILE: &str = "/tmp/demoterm.pid";
 const RECORDING_FILE: &str = "/tmp/demoterm_recording.json";
 const GIF_FILE: &str = "demoterm.gif";
+const CAST_FILE: &str = "demoterm.cast";
+
+/// Sidecar written next to `PID_FILE` recording the `--format` a session was
+/// actually started with, so `Stop` produces output for the format that was
+/// recorded instead of trusting a second, independently-supplied `--format`
+/// flag that might not match.
+const FORMAT_FILE: &str = "/tmp/demoterm_format";
+
+/// Delay given to the final GIF frame, which has no following event to
+/// measure a gap against.
+const DEFAULT_LAST_FRAME_DELAY_MS: u128 = 1000;
+
+// Default PTY grid, matched to a standard 80x24 terminal
+const TERM_ROWS: u16 = 24;
+const TERM_COLS: u16 = 80;
+
+/// asciicast format version we emit and read back.
+const ASCIICAST_VERSION: u8 = 2;
 
 // Command-line argument definitions
 #[derive(Parser)]
@@ -32,12 +55,64 @@ struct Cli {
     command: Commands,
 }
 
+/// Output format for a recording session.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// asciicast v2, playable by `demoterm play`/`cat` and other players
+    Asciicast,
+    /// Rendered GIF (the original behavior)
+    Gif,
+}
+
+/// Controls how `generate_gif` treats a `--digest` sidecar file: recording
+/// fresh per-frame hashes, verifying against previously recorded ones, or
+/// ignoring digesting entirely (the default).
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum DigestMode {
+    Record,
+    Verify,
+    Ignore,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Start terminal recording
-    Start,
-    /// Stop terminal recording and generate GIF
-    Stop,
+    Start {
+        /// Output format to record in
+        #[arg(long, value_enum, default_value_t = OutputFormat::Gif)]
+        format: OutputFormat,
+        /// Continue an existing asciicast recording instead of starting fresh
+        #[arg(long)]
+        append: bool,
+        /// Overwrite an existing asciicast recording
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Stop terminal recording and produce output
+    Stop {
+        /// Path to a frame-digest sidecar file (see --digest-mode)
+        #[arg(long)]
+        digest: Option<String>,
+        /// Record fresh per-frame digests to --digest, or verify against them
+        #[arg(long, value_enum, default_value_t = DigestMode::Ignore)]
+        digest_mode: DigestMode,
+        /// Cap any inter-event gap (e.g. idling at a prompt) to this many seconds
+        #[arg(long)]
+        idle_time_limit: Option<f64>,
+        /// Playback speed factor; every frame delay is divided by it (>1 plays back faster)
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+    /// Play back an asciicast recording in real time
+    Play {
+        /// Path to the asciicast file to play
+        path: String,
+    },
+    /// Dump an asciicast recording's output without real-time pacing
+    Cat {
+        /// Path to the asciicast file to dump
+        path: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -45,18 +120,209 @@ struct TerminalEvent {
     timestamp: u128, // Milliseconds since start
     input: Option<String>,
     output: Option<String>,
+    /// New (rows, cols) when the terminal was resized mid-session
+    #[serde(default)]
+    resize: Option<(u16, u16)>,
+}
+
+/// A recording loaded back from the journal or an asciicast file: the events
+/// themselves plus the grid they were captured at, so `generate_gif` can
+/// reconstruct a `vt100::Parser` sized to match instead of guessing.
+#[derive(Serialize, Deserialize, Debug)]
+struct Recording {
+    rows: u16,
+    cols: u16,
+    events: Vec<TerminalEvent>,
+}
+
+/// Header line of the internal (non-asciicast) recording journal: one of
+/// these, followed by one `TerminalEvent` JSON object per line, so the
+/// consumer in `run_recorder` can append incrementally instead of
+/// re-serializing the whole recording on every flush.
+#[derive(Serialize, Deserialize, Debug)]
+struct RecordingHeader {
+    rows: u16,
+    cols: u16,
+}
+
+/// Header line of an asciicast v2 file.
+#[derive(Serialize, Deserialize, Debug)]
+struct AsciicastHeader {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+    env: HashMap<String, String>,
+}
+
+/// An asciicast file, parsed back into its header and raw event lines. The
+/// event lines are kept verbatim (rather than re-parsed into `TerminalEvent`)
+/// so `--append` can splice them back out unchanged.
+struct ParsedAsciicast {
+    header: AsciicastHeader,
+    lines: Vec<String>,
+    last_time: f64,
+}
+
+/// Reads and parses an asciicast v2 file.
+fn read_asciicast_file(path: &Path) -> io::Result<ParsedAsciicast> {
+    let data = fs::read_to_string(path)?;
+    let mut lines = data.lines();
+    let header_line = lines
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "empty asciicast file"))?;
+    let header: AsciicastHeader = serde_json::from_str(header_line)?;
+
+    let mut event_lines = Vec::new();
+    let mut last_time = 0.0;
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (time, _code, _data): (f64, String, String) = serde_json::from_str(line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        last_time = time;
+        event_lines.push(line.to_string());
+    }
+
+    Ok(ParsedAsciicast {
+        header,
+        lines: event_lines,
+        last_time,
+    })
+}
+
+/// Converts a parsed asciicast file into the `Recording` shape `generate_gif`
+/// expects, so GIFs can be rendered from asciicasts recorded by demoterm
+/// itself or produced by another tool entirely.
+fn recording_from_asciicast(path: &Path) -> io::Result<Recording> {
+    let parsed = read_asciicast_file(path)?;
+    let mut events = Vec::with_capacity(parsed.lines.len());
+    for line in &parsed.lines {
+        let (time, code, data): (f64, String, String) = serde_json::from_str(&line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let timestamp = (time * 1000.0) as u128;
+        match code.as_str() {
+            "o" => events.push(TerminalEvent {
+                timestamp,
+                input: None,
+                output: Some(data),
+                resize: None,
+            }),
+            "i" => events.push(TerminalEvent {
+                timestamp,
+                input: Some(data),
+                output: None,
+                resize: None,
+            }),
+            "r" => {
+                if let Some((cols, rows)) = data
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse::<u16>().ok()?, h.parse::<u16>().ok()?)))
+                {
+                    events.push(TerminalEvent {
+                        timestamp,
+                        input: None,
+                        output: None,
+                        resize: Some((rows, cols)),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(Recording {
+        rows: parsed.header.height,
+        cols: parsed.header.width,
+        events,
+    })
+}
+
+/// Reads the internal recording journal back: a `RecordingHeader` line
+/// followed by one `TerminalEvent` JSON object per line.
+fn recording_from_journal(path: &Path) -> io::Result<Recording> {
+    let data = fs::read_to_string(path)?;
+    let mut lines = data.lines();
+    let header_line = lines
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "empty recording file"))?;
+    let header: RecordingHeader = serde_json::from_str(header_line)?;
+
+    let mut events = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(line)?);
+    }
+
+    Ok(Recording {
+        rows: header.rows,
+        cols: header.cols,
+        events,
+    })
+}
+
+/// Plays back an asciicast file's output events to stdout. When `real_time`
+/// is set, sleeps between events using their recorded timestamps (`play`);
+/// otherwise dumps every event as fast as possible (`cat`).
+fn play_asciicast(path: &str, real_time: bool) -> io::Result<()> {
+    let parsed = read_asciicast_file(Path::new(path))?;
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let mut prev_time = 0.0;
+    for line in &parsed.lines {
+        let (time, code, data): (f64, String, String) = serde_json::from_str(line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if code != "o" {
+            continue;
+        }
+        if real_time {
+            thread::sleep(Duration::from_secs_f64((time - prev_time).max(0.0)));
+        }
+        handle.write_all(data.as_bytes())?;
+        handle.flush()?;
+        prev_time = time;
+    }
+    Ok(())
 }
 
 fn main() -> io::Result<()> {
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Start => {
+        Commands::Start {
+            format,
+            append,
+            overwrite,
+        } => {
             if Path::new(PID_FILE).exists() {
                 eprintln!("Recording is already in progress.");
                 exit(1);
             }
 
+            if *append && *overwrite {
+                eprintln!("--append and --overwrite cannot be used together.");
+                exit(1);
+            }
+
+            if *format == OutputFormat::Asciicast
+                && Path::new(CAST_FILE).exists()
+                && !*append
+                && !*overwrite
+            {
+                eprintln!(
+                    "{} already exists. Use --append or --overwrite.",
+                    CAST_FILE
+                );
+                exit(1);
+            }
+
+            let format = *format;
+            let append = *append;
+
             // Fork the process into a background child
             match unsafe { nix::unistd::fork() } {
                 Ok(nix::unistd::ForkResult::Parent { .. }) => {
@@ -66,7 +332,7 @@ fn main() -> io::Result<()> {
                 }
                 Ok(nix::unistd::ForkResult::Child) => {
                     // Child process continues
-                    if let Err(e) = run_recorder() {
+                    if let Err(e) = run_recorder(format, append) {
                         eprintln!("Error in recorder: {}", e);
                         exit(1);
                     }
@@ -77,7 +343,12 @@ fn main() -> io::Result<()> {
                 }
             }
         }
-        Commands::Stop => {
+        Commands::Stop {
+            digest,
+            digest_mode,
+            idle_time_limit,
+            speed,
+        } => {
             if !Path::new(PID_FILE).exists() {
                 eprintln!("No recording session found.");
                 exit(1);
@@ -94,6 +365,19 @@ fn main() -> io::Result<()> {
             };
             let pid = Pid::from_raw(pid_num);
 
+            // The format to produce is whatever the session was actually
+            // started with, not a second, independently-supplied flag here,
+            // so Stop can't claim success for a format nothing was recorded
+            // in.
+            let format_str = fs::read_to_string(FORMAT_FILE)?;
+            let format = match OutputFormat::from_str(format_str.trim(), true) {
+                Ok(format) => format,
+                Err(_) => {
+                    eprintln!("Invalid format file.");
+                    exit(1);
+                }
+            };
+
             // Send SIGTERM to the recorder process
             if let Err(e) = kill(pid, Signal::SIGTERM) {
                 eprintln!("Failed to terminate recorder process: {}", e);
@@ -112,55 +396,165 @@ fn main() -> io::Result<()> {
                 exit(1);
             }
 
-            println!("Recording stopped. Generating GIF...");
+            let digest_path = digest.as_deref().map(Path::new);
+
+            match format {
+                OutputFormat::Gif => {
+                    println!("Recording stopped. Generating GIF...");
+                    let result = generate_gif(digest_path, *digest_mode, *idle_time_limit, *speed);
+
+                    // Cleanup recording file before reporting the outcome, so a
+                    // failure returned as an error (including a digest
+                    // verification mismatch) still leaves the journal cleaned
+                    // up like a success does. An empty recording exits inside
+                    // `generate_gif` and leaves the journal in place.
+                    let _ = fs::remove_file(RECORDING_FILE);
 
-            // Generate GIF from recording data
-            match generate_gif() {
-                Ok(_) => {
-                    println!("GIF generated as {}", GIF_FILE);
+                    match result {
+                        Ok(_) => {
+                            println!("GIF generated as {}", GIF_FILE);
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to generate GIF: {}", e);
+                            exit(1);
+                        }
+                    }
                 }
-                Err(e) => {
-                    eprintln!("Failed to generate GIF: {}", e);
-                    exit(1);
+                OutputFormat::Asciicast => {
+                    println!("Recording stopped. Asciicast saved as {}", CAST_FILE);
                 }
             }
-
-            // Cleanup recording file
-            let _ = fs::remove_file(RECORDING_FILE);
+        }
+        Commands::Play { path } => {
+            play_asciicast(path, true)?;
+        }
+        Commands::Cat { path } => {
+            play_asciicast(path, false)?;
         }
     }
 
     Ok(())
 }
 
+/// Internal event stream produced by the PTY reader, the stdin writer, and
+/// the signal handler, and consumed by a single owner of the recording. This
+/// replaces a shared `Arc<Mutex<Vec<TerminalEvent>>>`, so the hot read/write
+/// path never contends on a lock, and leaves a clean place to add future
+/// event sources (like the `Resize` variant below) without more shared state.
+/// Each variant except `Shutdown` carries the `start_time`-relative timestamp the producer
+/// captured at the moment the event actually happened, so a busy consumer
+/// (e.g. blocked on `append_event`'s file I/O) can't inflate the gap between
+/// events that were read off the PTY/stdin back-to-back.
+enum Event {
+    Output(u128, Vec<u8>),
+    Input(u128, Vec<u8>),
+    Resize(u128, u16, u16),
+    Shutdown,
+}
+
+/// Set by the SIGWINCH handler; polled by a dedicated thread that performs
+/// the actual PTY resize, since signal handlers must stay async-signal-safe.
+static RESIZE_PENDING: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_winch(_: i32) {
+    RESIZE_PENDING.store(true, Ordering::SeqCst);
+}
+
 /// Function to run the recorder in the background child process
-fn run_recorder() -> io::Result<()> {
+fn run_recorder(format: OutputFormat, append: bool) -> io::Result<()> {
     // Write PID to PID_FILE
     let pid = nix::unistd::getpid();
     fs::write(PID_FILE, pid.to_string())?;
 
-    // Set up signal handler for graceful termination
-    let running = Arc::new(Mutex::new(true));
+    // Record which format this session was started with, so `Stop` produces
+    // output for the format that was actually recorded.
+    let format_name = format
+        .to_possible_value()
+        .expect("OutputFormat has no skipped variants")
+        .get_name()
+        .to_string();
+    fs::write(FORMAT_FILE, format_name)?;
+
+    let (tx, rx) = mpsc::channel::<Event>();
+
+    // Signal handler for graceful termination: just another event producer
     {
-        let running = Arc::clone(&running);
+        let tx = tx.clone();
         ctrlc::set_handler(move || {
-            let mut running = running.lock().unwrap();
-            *running = false;
+            let _ = tx.send(Event::Shutdown);
         })
         .expect("Error setting Ctrl-C handler");
     }
 
-    // Initialize recording data
-    let events: Arc<Mutex<Vec<TerminalEvent>>> = Arc::new(Mutex::new(Vec::new()));
-    let events_clone = Arc::clone(&events);
     let start_time = std::time::Instant::now();
 
+    // When recording as asciicast, either start a fresh header or, with
+    // --append, carry over the previous file's header and event lines so new
+    // events continue from where it left off.
+    let (cast_header, cast_prior_lines, cast_time_offset) = if format == OutputFormat::Asciicast {
+        if append && Path::new(CAST_FILE).exists() {
+            let parsed = read_asciicast_file(Path::new(CAST_FILE))?;
+            (parsed.header, parsed.lines, parsed.last_time)
+        } else {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let mut env = HashMap::new();
+            env.insert("SHELL".to_string(), "/bin/bash".to_string());
+            (
+                AsciicastHeader {
+                    version: ASCIICAST_VERSION,
+                    width: TERM_COLS,
+                    height: TERM_ROWS,
+                    timestamp,
+                    env,
+                },
+                Vec::new(),
+                0.0,
+            )
+        }
+    } else {
+        (
+            AsciicastHeader {
+                version: ASCIICAST_VERSION,
+                width: TERM_COLS,
+                height: TERM_ROWS,
+                timestamp: 0,
+                env: HashMap::new(),
+            },
+            Vec::new(),
+            0.0,
+        )
+    };
+
+    // Write the journal's header (and any carried-over asciicast lines) up
+    // front, so the consumer below only ever has to append.
+    match format {
+        OutputFormat::Gif => {
+            let header = RecordingHeader {
+                rows: TERM_ROWS,
+                cols: TERM_COLS,
+            };
+            fs::write(RECORDING_FILE, format!("{}\n", serde_json::to_string(&header)?))?;
+        }
+        OutputFormat::Asciicast => {
+            let mut out = serde_json::to_string(&cast_header)?;
+            out.push('\n');
+            for line in &cast_prior_lines {
+                out.push_str(line);
+                out.push('\n');
+            }
+            fs::write(CAST_FILE, out)?;
+        }
+    }
+
     // Initialize PTY and spawn shell
     let pty_system = native_pty_system();
     let pair = pty_system
         .openpty(PtySize {
-            rows: 24,
-            cols: 80,
+            rows: TERM_ROWS,
+            cols: TERM_COLS,
             pixel_width: 0,
             pixel_height: 0,
         })
@@ -171,10 +565,50 @@ fn run_recorder() -> io::Result<()> {
         .spawn_command(CommandBuilder::new("/bin/bash"))
         .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
-    // Reader thread: reads from PTY and records output
-    let reader_events = Arc::clone(&events_clone);
-    let mut reader = pair
-        .master
+    // Shared so the resize-watcher thread below can call `resize` on it
+    // alongside the reader/writer threads. `MasterPty` isn't `Sync`, so a
+    // bare `Arc<Box<dyn MasterPty + Send>>` can't cross the thread boundary
+    // on its own; the `Mutex` supplies the `Sync` that's missing, and since
+    // `resize` is only ever called from the watcher thread below, there's no
+    // contention to worry about.
+    let master: Arc<Mutex<Box<dyn MasterPty + Send>>> = Arc::new(Mutex::new(pair.master));
+
+    // Watch for SIGWINCH and mirror the real terminal's size onto the PTY,
+    // recording a resize event so `generate_gif` can replay it later.
+    unsafe { nix::sys::signal::signal(Signal::SIGWINCH, SigHandler::Handler(handle_winch)) }
+        .expect("Error setting SIGWINCH handler");
+    {
+        let resize_tx = tx.clone();
+        let master = Arc::clone(&master);
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_millis(100));
+            if !RESIZE_PENDING.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+            if let Some((Width(cols), Height(rows))) = terminal_size() {
+                let resized = master
+                    .lock()
+                    .unwrap()
+                    .resize(PtySize {
+                        rows,
+                        cols,
+                        pixel_width: 0,
+                        pixel_height: 0,
+                    })
+                    .is_ok();
+                if resized {
+                    let timestamp = start_time.elapsed().as_millis();
+                    let _ = resize_tx.send(Event::Resize(timestamp, rows, cols));
+                }
+            }
+        });
+    }
+
+    // Reader thread: reads from PTY and forwards output events
+    let reader_tx = tx.clone();
+    let mut reader = master
+        .lock()
+        .unwrap()
         .try_clone_reader()
         .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
     thread::spawn(move || {
@@ -183,23 +617,25 @@ fn run_recorder() -> io::Result<()> {
             match reader.read(&mut buffer) {
                 Ok(0) => break, // EOF
                 Ok(n) => {
-                    let output = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    let mut events = reader_events.lock().unwrap();
-                    events.push(TerminalEvent {
-                        timestamp: start_time.elapsed().as_millis(),
-                        input: None,
-                        output: Some(output),
-                    });
+                    let timestamp = start_time.elapsed().as_millis();
+                    if reader_tx
+                        .send(Event::Output(timestamp, buffer[..n].to_vec()))
+                        .is_err()
+                    {
+                        break;
+                    }
                 }
                 Err(_) => break,
             }
         }
     });
 
-    // Writer thread: reads user input and sends to PTY
-    let writer_events = Arc::clone(&events_clone);
-    let mut writer = pair
-        .master
+    // Writer thread: reads user input, sends it to the PTY, and forwards
+    // input events
+    let writer_tx = tx.clone();
+    let mut writer = master
+        .lock()
+        .unwrap()
         .take_writer()
         .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
     thread::spawn(move || {
@@ -215,35 +651,51 @@ fn run_recorder() -> io::Result<()> {
                         eprintln!("Failed to write to PTY: {}", e);
                         break;
                     }
-                    let input_str = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    let mut events = writer_events.lock().unwrap();
-                    events.push(TerminalEvent {
-                        timestamp: start_time.elapsed().as_millis(),
-                        input: Some(input_str),
-                        output: None,
-                    });
+                    let timestamp = start_time.elapsed().as_millis();
+                    if writer_tx
+                        .send(Event::Input(timestamp, buffer[..n].to_vec()))
+                        .is_err()
+                    {
+                        break;
+                    }
                 }
                 Err(_) => break,
             }
         }
     });
 
-    // Periodically save recording data
-    let save_events = Arc::clone(&events_clone);
-    thread::spawn(move || loop {
-        thread::sleep(Duration::from_secs(5));
-        let data = save_events.lock().unwrap();
-        if data.is_empty() {
-            continue;
-        }
-        if let Ok(json) = serde_json::to_string(&*data) {
-            let _ = fs::write(RECORDING_FILE, json);
-        }
-    });
+    // Drop the original sender; the reader/writer/signal-handler clones keep
+    // the channel alive, and the consumer loop below terminates on Shutdown
+    // rather than on the channel closing.
+    drop(tx);
 
-    // Main loop: keep running until termination signal
-    while *running.lock().unwrap() {
-        thread::sleep(Duration::from_millis(100));
+    // Single consumer: appends each event to the on-disk journal as it
+    // arrives instead of periodically re-serializing everything recorded so
+    // far. No in-memory list of events is kept, and `append_event` reopens
+    // the journal file for every event.
+    for event in rx.iter() {
+        let terminal_event = match event {
+            Event::Output(timestamp, bytes) => TerminalEvent {
+                timestamp,
+                input: None,
+                output: Some(String::from_utf8_lossy(&bytes).to_string()),
+                resize: None,
+            },
+            Event::Input(timestamp, bytes) => TerminalEvent {
+                timestamp,
+                input: Some(String::from_utf8_lossy(&bytes).to_string()),
+                output: None,
+                resize: None,
+            },
+            Event::Resize(timestamp, rows, cols) => TerminalEvent {
+                timestamp,
+                input: None,
+                output: None,
+                resize: Some((rows, cols)),
+            },
+            Event::Shutdown => break,
+        };
+        append_event(format, &terminal_event, cast_time_offset)?;
     }
 
     // Terminate the shell process
@@ -252,24 +704,59 @@ fn run_recorder() -> io::Result<()> {
     // Wait for the shell to exit
     let _ = shell.wait();
 
-    // Serialize recording data to RECORDING_FILE
-    let recorded_events = events.lock().unwrap();
-    let json = serde_json::to_string(&*recorded_events)?;
-    fs::write(RECORDING_FILE, json)?;
-
-    // Remove PID_FILE
+    // Remove PID_FILE and its format sidecar
     fs::remove_file(PID_FILE)?;
+    let _ = fs::remove_file(FORMAT_FILE);
 
     Ok(())
 }
 
+/// Appends a single recorded event to the on-disk journal, matching
+/// whichever format the session was started with.
+fn append_event(format: OutputFormat, event: &TerminalEvent, time_offset_secs: f64) -> io::Result<()> {
+    match format {
+        OutputFormat::Gif => {
+            let mut file = OpenOptions::new().append(true).open(RECORDING_FILE)?;
+            writeln!(file, "{}", serde_json::to_string(event)?)
+        }
+        OutputFormat::Asciicast => {
+            let mut file = OpenOptions::new().append(true).open(CAST_FILE)?;
+            let secs = event.timestamp as f64 / 1000.0 + time_offset_secs;
+            if let Some(input) = &event.input {
+                writeln!(file, "{}", serde_json::to_string(&(secs, "i", input))?)?;
+            }
+            if let Some(output) = &event.output {
+                writeln!(file, "{}", serde_json::to_string(&(secs, "o", output))?)?;
+            }
+            if let Some((rows, cols)) = event.resize {
+                // asciicast v2 resize events carry "{width}x{height}" data
+                writeln!(file, "{}", serde_json::to_string(&(secs, "r", format!("{}x{}", cols, rows)))?)?;
+            }
+            Ok(())
+        }
+    }
+}
+
 /// Function to generate GIF from recorded terminal events
-fn generate_gif() -> io::Result<()> {
-    // Read recording data
-    let data = fs::read_to_string(RECORDING_FILE)?;
-    let events: Vec<TerminalEvent> = serde_json::from_str(&data)?;
+fn generate_gif(
+    digest_path: Option<&Path>,
+    digest_mode: DigestMode,
+    idle_time_limit: Option<f64>,
+    speed: f64,
+) -> io::Result<()> {
+    // Read recording data. RECORDING_FILE only ever exists for the Gif
+    // session that's actually finishing right now (Stop removes it
+    // afterward), so it takes priority; CAST_FILE is never cleaned up and
+    // would otherwise keep getting picked up by every later Gif session.
+    // Falling back to it lets GIFs also be rendered from asciicasts
+    // captured elsewhere by simply dropping them at CAST_FILE.
+    let recording: Recording = if Path::new(RECORDING_FILE).exists() {
+        recording_from_journal(Path::new(RECORDING_FILE))?
+    } else {
+        recording_from_asciicast(Path::new(CAST_FILE))?
+    };
 
-    if events.is_empty() {
+    if recording.events.is_empty() {
         eprintln!("No events recorded.");
         exit(1);
     }
@@ -280,29 +767,45 @@ fn generate_gif() -> io::Result<()> {
     let font_data = fs::read(font_path).expect("Failed to read font file.");
     let font = Font::try_from_vec(font_data).expect("Failed to load font.");
 
-    // Define image parameters
+    // Define image parameters. The canvas is sized to the largest grid the
+    // recording ever used, so a resize mid-session replays without clipping.
     let scale = Scale { x: 20.0, y: 20.0 };
-    let image_width = 800;
-    let image_height = 600;
+    let (mut max_rows, mut max_cols) = (recording.rows, recording.cols);
+    for event in &recording.events {
+        if let Some((rows, cols)) = event.resize {
+            max_rows = max_rows.max(rows);
+            max_cols = max_cols.max(cols);
+        }
+    }
+    let image_width = (max_cols as f32 * scale.x * 0.6 + 20.0) as u32;
+    let image_height = (max_rows as f32 * (scale.y + 5.0) + scale.y) as u32;
 
-    // Create a vector to hold frames
-    let mut frames = Vec::new();
+    // Create a vector to hold (timestamp, frame) pairs, the timestamps are
+    // kept so real per-frame delays can be computed below.
+    let mut frames: Vec<(u128, RgbImage)> = Vec::new();
 
-    // Initialize screen buffer
-    let mut screen = String::new();
+    // Feed each event's raw output through a real terminal emulator so
+    // escape sequences (colors, cursor movement, screen clears) are
+    // interpreted instead of dumped onto the image as text.
+    let mut parser = vt100::Parser::new(recording.rows, recording.cols, 0);
 
     // Iterate over events and render to images
-    for event in events {
-        if let Some(input) = event.input {
-            screen.push_str(&input);
+    for event in recording.events {
+        let timestamp = event.timestamp;
+        if let Some((rows, cols)) = event.resize {
+            parser.set_size(rows, cols);
         }
         if let Some(output) = event.output {
-            screen.push_str(&output);
+            parser.process(output.as_bytes());
         }
 
-        // Render current screen to image
-        let img = render_text_to_image(&screen, &font, scale, image_width, image_height)?;
-        frames.push(img);
+        // Render the emulator's current screen to an image
+        let img = render_screen_to_image(parser.screen(), &font, scale, image_width, image_height)?;
+        frames.push((timestamp, img));
+    }
+
+    if let Some(digest_path) = digest_path {
+        verify_or_record_digests(digest_path, digest_mode, &frames)?;
     }
 
     // Create GIF
@@ -313,11 +816,28 @@ fn generate_gif() -> io::Result<()> {
         .set_repeat(Repeat::Infinite)
         .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
-    for frame in frames {
-        let mut buffer: Vec<u8> = Vec::new();
-        let mut cursor = std::io::Cursor::new(&mut buffer);
-        let gif_frame =
-            GifFrame::from_rgb_speed(image_width as u16, image_height as u16, &frame, 10);
+    let idle_limit_ms = idle_time_limit.map(|secs| (secs * 1000.0).max(0.0) as u128);
+
+    for (i, (timestamp, frame)) in frames.iter().enumerate() {
+        let mut gif_frame =
+            GifFrame::from_rgb_speed(image_width as u16, image_height as u16, frame, 10);
+
+        // Delay this frame by the gap to the next event, capped by
+        // --idle-time-limit and scaled by --speed; the final frame has no
+        // following event, so it gets a flat default delay.
+        let delay_ms = match frames.get(i + 1) {
+            Some((next_timestamp, _)) => {
+                let mut gap = next_timestamp.saturating_sub(*timestamp);
+                if let Some(limit) = idle_limit_ms {
+                    gap = gap.min(limit);
+                }
+                gap
+            }
+            None => DEFAULT_LAST_FRAME_DELAY_MS,
+        };
+        let delay_cs = ((delay_ms as f64 / speed) / 10.0).round().clamp(1.0, u16::MAX as f64);
+        gif_frame.delay = delay_cs as u16;
+
         encoder
             .write_frame(&gif_frame)
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
@@ -326,38 +846,221 @@ fn generate_gif() -> io::Result<()> {
     Ok(())
 }
 
-/// Function to render text to an RGB image
-fn render_text_to_image(
-    text: &str,
+// Default foreground/background used for cells that don't specify a color.
+const DEFAULT_FG: Rgb<u8> = Rgb([229, 229, 229]);
+const DEFAULT_BG: Rgb<u8> = Rgb([0, 0, 0]);
+
+const ANSI_COLORS: [Rgb<u8>; 16] = [
+    Rgb([0, 0, 0]),
+    Rgb([205, 0, 0]),
+    Rgb([0, 205, 0]),
+    Rgb([205, 205, 0]),
+    Rgb([0, 0, 238]),
+    Rgb([205, 0, 205]),
+    Rgb([0, 205, 205]),
+    Rgb([229, 229, 229]),
+    Rgb([127, 127, 127]),
+    Rgb([255, 0, 0]),
+    Rgb([0, 255, 0]),
+    Rgb([255, 255, 0]),
+    Rgb([92, 92, 255]),
+    Rgb([255, 0, 255]),
+    Rgb([0, 255, 255]),
+    Rgb([255, 255, 255]),
+];
+
+/// Maps an xterm 256-color palette index to an RGB triple.
+fn xterm_256_to_rgb(idx: u8) -> Rgb<u8> {
+    match idx {
+        0..=15 => ANSI_COLORS[idx as usize],
+        16..=231 => {
+            let i = idx - 16;
+            let r = i / 36;
+            let g = (i % 36) / 6;
+            let b = i % 6;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            Rgb([level(r), level(g), level(b)])
+        }
+        232..=255 => {
+            let level = 8 + (idx - 232) * 10;
+            Rgb([level, level, level])
+        }
+    }
+}
+
+/// Resolves a `vt100::Color` to a concrete RGB value, falling back to
+/// `default` for `Color::Default`.
+fn resolve_color(color: vt100::Color, default: Rgb<u8>) -> Rgb<u8> {
+    match color {
+        vt100::Color::Default => default,
+        vt100::Color::Idx(idx) => xterm_256_to_rgb(idx),
+        vt100::Color::Rgb(r, g, b) => Rgb([r, g, b]),
+    }
+}
+
+/// Function to render a vt100 screen grid to an RGB image. Each cell is
+/// drawn as a filled background rectangle with its glyph on top, honoring
+/// bold/inverse attributes, and the cursor is drawn as a solid block.
+fn render_screen_to_image(
+    screen: &vt100::Screen,
     font: &Font,
     scale: Scale,
     width: u32,
     height: u32,
 ) -> io::Result<RgbImage> {
-    // Create a blank black image
-    let mut image = RgbImage::from_pixel(width, height, Rgb([0, 0, 0]));
-
-    // Position to start drawing text
-    let mut x = 10.0;
-    let mut y = scale.y;
-
-    for line in text.lines() {
-        for glyph in font.layout(line, scale, rusttype::point(x, y)) {
-            if let Some(bounding_box) = glyph.pixel_bounding_box() {
-                glyph.draw(|gx, gy, gv| {
-                    let px = gx + bounding_box.min.x as u32;
-                    let py = gy + bounding_box.min.y as u32;
-                    if px < width && py < height {
-                        let pixel = image.get_pixel_mut(px, py);
-                        let intensity = (gv * 255.0) as u8;
-                        // Simple white text
-                        *pixel = Rgb([intensity, intensity, intensity]);
+    let mut image = RgbImage::from_pixel(width, height, DEFAULT_BG);
+
+    let cell_width = scale.x * 0.6;
+    let cell_height = scale.y + 5.0;
+    let origin_x = 10.0;
+    let origin_y = scale.y;
+
+    let (rows, cols) = screen.size();
+    for row in 0..rows {
+        for col in 0..cols {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+
+            let mut fg = resolve_color(cell.fgcolor(), DEFAULT_FG);
+            let mut bg = resolve_color(cell.bgcolor(), DEFAULT_BG);
+            if cell.inverse() {
+                std::mem::swap(&mut fg, &mut bg);
+            }
+
+            let cell_x = origin_x + col as f32 * cell_width;
+            let cell_y = origin_y - scale.y + row as f32 * cell_height;
+
+            fill_rect(&mut image, cell_x, cell_y, cell_width, cell_height, bg, width, height);
+
+            let contents = cell.contents();
+            if contents.is_empty() {
+                continue;
+            }
+
+            for glyph in font.layout(&contents, scale, rusttype::point(cell_x, cell_y + scale.y)) {
+                if let Some(bounding_box) = glyph.pixel_bounding_box() {
+                    glyph.draw(|gx, gy, gv| {
+                        let px = gx as i32 + bounding_box.min.x;
+                        let py = gy as i32 + bounding_box.min.y;
+                        if px >= 0 && py >= 0 && (px as u32) < width && (py as u32) < height {
+                            let pixel = image.get_pixel_mut(px as u32, py as u32);
+                            let blend = |c: u8, b: u8| {
+                                (c as f32 * gv + b as f32 * (1.0 - gv)) as u8
+                            };
+                            *pixel = Rgb([
+                                blend(fg.0[0], bg.0[0]),
+                                blend(fg.0[1], bg.0[1]),
+                                blend(fg.0[2], bg.0[2]),
+                            ]);
+                        }
+                    });
+                }
+            }
+
+            if cell.bold() {
+                // Cheap bold emulation: redraw the glyph offset by one pixel.
+                for glyph in
+                    font.layout(&contents, scale, rusttype::point(cell_x + 1.0, cell_y + scale.y))
+                {
+                    if let Some(bounding_box) = glyph.pixel_bounding_box() {
+                        glyph.draw(|gx, gy, gv| {
+                            let px = gx as i32 + bounding_box.min.x;
+                            let py = gy as i32 + bounding_box.min.y;
+                            if px >= 0 && py >= 0 && (px as u32) < width && (py as u32) < height && gv > 0.5 {
+                                image.put_pixel(px as u32, py as u32, fg);
+                            }
+                        });
                     }
-                });
+                }
             }
         }
-        y += scale.y + 5.0; // Move to next line
+    }
+
+    if !screen.hide_cursor() {
+        let (cursor_row, cursor_col) = screen.cursor_position();
+        let cx = origin_x + cursor_col as f32 * cell_width;
+        let cy = origin_y - scale.y + cursor_row as f32 * cell_height;
+        fill_rect(&mut image, cx, cy, cell_width, cell_height, DEFAULT_FG, width, height);
     }
 
     Ok(image)
 }
+
+/// Fills an axis-aligned rectangle, clipping against the image bounds.
+fn fill_rect(image: &mut RgbImage, x: f32, y: f32, w: f32, h: f32, color: Rgb<u8>, width: u32, height: u32) {
+    let x0 = x.max(0.0) as u32;
+    let y0 = y.max(0.0) as u32;
+    let x1 = ((x + w) as u32).min(width);
+    let y1 = ((y + h) as u32).min(height);
+    for py in y0..y1 {
+        for px in x0..x1 {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// Fast non-cryptographic hash (FNV-1a) over a frame's raw RGB buffer, used
+/// for golden-file frame digests rather than committing binary GIFs.
+fn fnv1a_hash(data: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let mut hash = FNV_OFFSET;
+    for &byte in data {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Records or verifies per-frame digests against a sidecar file, depending
+/// on `mode`. In `Verify` mode, a mismatching frame count or hash is a hard
+/// error so regressions in the rendering pipeline (font metrics, color
+/// handling, vt100 output) fail loudly instead of silently.
+fn verify_or_record_digests(
+    path: &Path,
+    mode: DigestMode,
+    frames: &[(u128, RgbImage)],
+) -> io::Result<()> {
+    match mode {
+        DigestMode::Ignore => Ok(()),
+        DigestMode::Record => {
+            let mut out = String::new();
+            for (_, frame) in frames {
+                out.push_str(&format!("{:016x}\n", fnv1a_hash(frame.as_raw())));
+            }
+            fs::write(path, out)
+        }
+        DigestMode::Verify => {
+            let expected = fs::read_to_string(path)?;
+            let expected: Vec<&str> = expected.lines().collect();
+
+            if expected.len() != frames.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "frame digest mismatch: expected {} frames, rendered {}",
+                        expected.len(),
+                        frames.len()
+                    ),
+                ));
+            }
+
+            for (i, (_, frame)) in frames.iter().enumerate() {
+                let actual = format!("{:016x}", fnv1a_hash(frame.as_raw()));
+                if actual != expected[i] {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "frame digest mismatch at frame {}: expected {}, got {}",
+                            i, expected[i], actual
+                        ),
+                    ));
+                }
+            }
+
+            println!("Verified {} frame digests against {}.", frames.len(), path.display());
+            Ok(())
+        }
+    }
+}